@@ -0,0 +1,22 @@
+use ethers_contract::Abigen;
+
+fn main() {
+    println!("cargo:rerun-if-changed=abi/TaikoL1.json");
+    println!("cargo:rerun-if-changed=abi/AssignmentHook.json");
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is not set");
+
+    Abigen::new("TaikoL1", "abi/TaikoL1.json")
+        .expect("invalid TaikoL1 ABI")
+        .generate()
+        .expect("failed to generate TaikoL1 bindings")
+        .write_to_file(format!("{out_dir}/taiko_l1.rs"))
+        .expect("failed to write TaikoL1 bindings");
+
+    Abigen::new("AssignmentHook", "abi/AssignmentHook.json")
+        .expect("invalid AssignmentHook ABI")
+        .generate()
+        .expect("failed to generate AssignmentHook bindings")
+        .write_to_file(format!("{out_dir}/assignment_hook.rs"))
+        .expect("failed to write AssignmentHook bindings");
+}
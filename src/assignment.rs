@@ -1,13 +1,23 @@
+use std::net::SocketAddr;
 use std::ops::Add;
 
+use axum::extract::ConnectInfo;
+use axum::http::HeaderMap;
 use axum::{debug_handler, extract::State, http::StatusCode, routing::post, Json, Router};
 use chrono::Utc;
 use ethereum_types::{H160, H256};
+use ethers_core::abi::{encode, Token};
+use ethers_core::types::U256;
+use ethers_core::utils::keccak256;
+use ethers_providers::Middleware;
+use k256::ecdsa::{RecoveryId, Signature};
 use serde::{ser::SerializeStruct, Deserialize, Serialize};
 use tracing::{info, warn};
 use utoipa::{OpenApi, ToSchema};
 
-use crate::{ApiResult, AppState, Tier};
+use crate::db::AssignmentRecord;
+use crate::events::AssignmentEvent;
+use crate::{client_ip, ApiResult, AppState, Tier};
 
 #[derive(Debug, Deserialize, ToSchema)]
 struct TierFee {
@@ -66,11 +76,17 @@ impl Serialize for ProposeBlockResponse {
     tag = "assignment",
     responses(
         (status = 200, description = "Create a proof assignment", body = ProposeBlockResponse),
-        (status = 422, description = "Unprocessable entity", body = String, examples( 
-            ("InvalidTxListHash" = (value = json!("invalid txList hash"))), 
-            ("OnlyETH" = (value = json!("only receive ETH"))), 
-            ("ProofFeeLow" = (value = json!("proof fee too low"))), 
+        (status = 422, description = "Unprocessable entity", body = String, examples(
+            ("InvalidTxListHash" = (value = json!("invalid txList hash"))),
+            ("OnlyETH" = (value = json!("only receive ETH"))),
+            ("InsufficientProverBalance" = (value = json!("insufficient prover balance"))),
+            ("ProofFeeLow" = (value = json!("proof fee too low"))),
             ("ExpiryTooLong" = (value = json!("expiry too long"))),
+            ("AlreadyAssigned" = (value = json!("txList already assigned"))),
+        )),
+        (status = 429, description = "Too many requests", body = String, examples(
+            ("ProverAtCapacity" = (value = json!("prover at capacity"))),
+            ("RateLimited" = (value = json!("too many requests"))),
         )),
     ),
     request_body = CreateAssignmentRequestBody,
@@ -78,8 +94,25 @@ impl Serialize for ProposeBlockResponse {
 #[debug_handler(state = AppState)]
 async fn create_assignment(
     State(state): State<AppState>,
+    ConnectInfo(connect_info): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(req): Json<CreateAssignmentRequestBody>,
 ) -> ApiResult<ProposeBlockResponse> {
+    let proposer_ip = client_ip(connect_info, &headers, &state.trusted_proxies);
+
+    if !state.check_rate_limit(proposer_ip) {
+        warn!(
+            target: "create_assignment",
+            description = "Proposer rate limited",
+            proposerIP = proposer_ip.to_string(),
+        );
+
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            "too many requests".to_string(),
+        ));
+    }
+
     info!(
         target: "create_assignment",
         description = "Proof assignment request body",
@@ -92,8 +125,20 @@ async fn create_assignment(
             .collect::<Vec<String>>()
             .join(", "),
         txListHash = req.tx_list_hash.to_string(),
+        proposerIP = proposer_ip.to_string(),
     );
 
+    let _permit = state
+        .propose_concurrency_guard
+        .clone()
+        .try_acquire_owned()
+        .map_err(|_| {
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                "prover at capacity".to_string(),
+            )
+        })?;
+
     if req.tx_list_hash.is_zero() {
         return Err((
             StatusCode::UNPROCESSABLE_ENTITY,
@@ -109,10 +154,29 @@ async fn create_assignment(
     }
 
     if !state.is_guardian {
-        // TODO: check prover balance
+        let balance = state
+            .rpc
+            .get_balance(state.prover_address, None)
+            .await
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+
+        if balance < U256::from(state.liveness_bond) {
+            warn!(
+                target: "create_assignment",
+                description = "Insufficient prover balance",
+                balance = balance.to_string(),
+                livenessBond = state.liveness_bond,
+                proposerIP = proposer_ip.to_string(),
+            );
+
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "insufficient prover balance".to_string(),
+            ));
+        }
     }
 
-    for tier in req.tier_fees {
+    for tier in &req.tier_fees {
         if tier.tier == Tier::Guardian {
             continue;
         }
@@ -132,8 +196,7 @@ async fn create_assignment(
                 tier = tier.tier.to_string(),
                 fee = tier.fee,
                 minTierFee = min_fee,
-                proposerIP = "TODO",
-                // TODO: get proposer IP
+                proposerIP = proposer_ip.to_string(),
             );
 
             return Err((
@@ -151,8 +214,7 @@ async fn create_assignment(
             description = "Expiry too long",
             expiry= req.expiry,
             srvMaxExpiry= state.max_expiry,
-            proposerIP = "TODO",
-            // TODO: get proposer IP
+            proposerIP = proposer_ip.to_string(),
         );
 
         return Err((
@@ -161,22 +223,144 @@ async fn create_assignment(
         ));
     }
 
-    // TODO: check if prover has any capacity
+    let l1_head = state
+        .rpc
+        .get_block_number()
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    let min_block_id = l1_head.as_u64() + state.max_slippage;
+
+    let max_block_id = state
+        .assignments
+        .reserve(
+            min_block_id,
+            &AssignmentRecord {
+                tx_list_hash: req.tx_list_hash,
+                prover: state.prover_address,
+                expiry: req.expiry,
+                tier_fees: req
+                    .tier_fees
+                    .iter()
+                    .map(|tf| tf.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            },
+        )
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+        .ok_or_else(|| {
+            warn!(
+                target: "create_assignment",
+                description = "txList already assigned",
+                txListHash = req.tx_list_hash.to_string(),
+                proposerIP = proposer_ip.to_string(),
+            );
 
-    // TODO: get L1 block head
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "txList already assigned".to_string(),
+            )
+        })?;
+
+    let encoded_assignment = encode_assignment(&req, max_block_id, state.max_proposed_in);
+    let digest = signing_digest(
+        state.taiko_l1_address,
+        req.tx_list_hash,
+        &encoded_assignment,
+    );
+    let signed_payload = match sign_digest(&state.prover_private_key, digest) {
+        Ok(signed_payload) => signed_payload,
+        Err(err) => {
+            let _ = state.assignments.release(max_block_id).await;
+            return Err(err);
+        }
+    };
 
-    // TODO: encode assignment payload
+    state
+        .assignments
+        .set_signed_payload(max_block_id, &signed_payload)
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
 
-    // TODO: sign encoded payload
+    let _ = state.assignment_events.send(AssignmentEvent::Assigned {
+        tx_list_hash: req.tx_list_hash,
+    });
 
     Ok(Json(ProposeBlockResponse {
-        signed_payload: vec![],
-        prover: H160::zero(),
-        max_block_id: 0,
-        max_proposed_in: 0,
+        signed_payload,
+        prover: state.prover_address,
+        max_block_id,
+        max_proposed_in: state.max_proposed_in,
     }))
 }
 
+/// ABI-encodes the assignment struct signed off-chain by the prover:
+/// `(feeToken, tierFees[], expiry, maxBlockId, maxProposedIn, txListHash)`.
+fn encode_assignment(
+    req: &CreateAssignmentRequestBody,
+    max_block_id: u64,
+    max_proposed_in: u64,
+) -> Vec<u8> {
+    let tier_fees = req
+        .tier_fees
+        .iter()
+        .map(|tier_fee| {
+            Token::Tuple(vec![
+                Token::Uint((tier_fee.tier as u16).into()),
+                Token::Uint((tier_fee.fee as u128).into()),
+            ])
+        })
+        .collect();
+
+    encode(&[
+        Token::Address(req.fee_token),
+        Token::Array(tier_fees),
+        Token::Uint(req.expiry.into()),
+        Token::Uint(max_block_id.into()),
+        Token::Uint(max_proposed_in.into()),
+        Token::FixedBytes(req.tx_list_hash.as_bytes().to_vec()),
+    ])
+}
+
+/// Mirrors the on-chain `AssignmentHook::hashAssignment`'s digest:
+/// `keccak256(abi.encodePacked(encodedAssignment, taikoL1Address, txListHash))`.
+fn signing_digest(
+    taiko_l1_address: H160,
+    tx_list_hash: H256,
+    encoded_assignment: &[u8],
+) -> [u8; 32] {
+    let mut packed =
+        Vec::with_capacity(encoded_assignment.len() + taiko_l1_address.as_bytes().len() + 32);
+    packed.extend_from_slice(encoded_assignment);
+    packed.extend_from_slice(taiko_l1_address.as_bytes());
+    packed.extend_from_slice(tx_list_hash.as_bytes());
+
+    keccak256(packed)
+}
+
+/// Signs a 32-byte digest with a recoverable secp256k1 signature and
+/// serializes it as the 65-byte `r‖s‖v` payload expected by the hook,
+/// with `v` normalized to 27/28.
+fn sign_digest(
+    signing_key: &k256::ecdsa::SigningKey,
+    digest: [u8; 32],
+) -> Result<Vec<u8>, (StatusCode, String)> {
+    let (signature, recovery_id): (Signature, RecoveryId) = signing_key
+        .sign_prehash_recoverable(&digest)
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to sign assignment".to_string(),
+            )
+        })?;
+
+    let mut signed_payload = Vec::with_capacity(65);
+    signed_payload.extend_from_slice(&signature.to_bytes());
+    signed_payload.push(recovery_id.to_byte() + 27);
+
+    Ok(signed_payload)
+}
+
 #[derive(OpenApi)]
 #[openapi(
     paths(create_assignment),
@@ -187,3 +371,44 @@ pub struct AssignmentDoc;
 pub fn create_router() -> Router<AppState> {
     Router::new().route("/", post(create_assignment))
 }
+
+#[cfg(test)]
+mod tests {
+    use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+
+    use super::*;
+
+    #[test]
+    fn signing_digest_packs_encoded_assignment_then_taiko_l1_address_then_tx_list_hash() {
+        let taiko_l1_address = H160::repeat_byte(0x11);
+        let tx_list_hash = H256::repeat_byte(0x22);
+        let encoded_assignment = vec![0xAA, 0xBB, 0xCC];
+
+        let digest = signing_digest(taiko_l1_address, tx_list_hash, &encoded_assignment);
+
+        let mut expected_packed = encoded_assignment.clone();
+        expected_packed.extend_from_slice(taiko_l1_address.as_bytes());
+        expected_packed.extend_from_slice(tx_list_hash.as_bytes());
+
+        assert_eq!(digest, keccak256(expected_packed));
+    }
+
+    #[test]
+    fn sign_digest_produces_a_signature_recoverable_to_the_signer() {
+        let signing_key = SigningKey::from_slice(&[7u8; 32]).expect("valid key");
+        let digest = [9u8; 32];
+
+        let signed_payload = sign_digest(&signing_key, digest).expect("signing should succeed");
+        assert_eq!(signed_payload.len(), 65);
+
+        let signature =
+            Signature::from_slice(&signed_payload[..64]).expect("valid signature bytes");
+        let recovery_id =
+            RecoveryId::from_byte(signed_payload[64] - 27).expect("valid recovery id");
+
+        let recovered = VerifyingKey::recover_from_prehash(&digest, &signature, recovery_id)
+            .expect("should recover verifying key");
+
+        assert_eq!(recovered, *signing_key.verifying_key());
+    }
+}
@@ -0,0 +1,6 @@
+//! Typed contract bindings generated at build time (see `build.rs`) from the
+//! ABI JSON committed under `abi/`.
+#![allow(clippy::all)]
+
+include!(concat!(env!("OUT_DIR"), "/taiko_l1.rs"));
+include!(concat!(env!("OUT_DIR"), "/assignment_hook.rs"));
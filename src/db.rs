@@ -0,0 +1,260 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use ethereum_types::{H160, H256};
+use sqlx::any::{install_default_drivers, AnyPoolOptions};
+use sqlx::AnyPool;
+use tracing::{error, info};
+
+/// How long a reservation may sit with `signed_payload IS NULL` before it's
+/// treated as abandoned (e.g. the process died between [`AssignmentStore::reserve`]
+/// and [`AssignmentStore::set_signed_payload`]) and its `txListHash` becomes
+/// reclaimable again.
+const STALE_RESERVATION_TIMEOUT_MS: i64 = 30_000;
+
+/// An assignment the prover is about to sign, persisted ahead of signing so
+/// `txListHash` replay and `maxBlockId` allocation are enforced by the
+/// database rather than read-then-write application logic.
+pub struct AssignmentRecord {
+    pub tx_list_hash: H256,
+    pub prover: H160,
+    pub expiry: u64,
+    pub tier_fees: String,
+}
+
+/// Persists issued assignments behind `DATABASE_URL` (SQLite or Postgres,
+/// picked by `sqlx`'s `Any` driver from the URL scheme).
+#[derive(Debug, Clone)]
+pub struct AssignmentStore {
+    pool: AnyPool,
+}
+
+impl AssignmentStore {
+    pub async fn connect(database_url: &str) -> anyhow::Result<Self> {
+        install_default_drivers();
+
+        let pool = AnyPoolOptions::new().connect(database_url).await?;
+
+        sqlx::migrate!("./migrations").run(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    /// Atomically allocates the next `max_block_id` (at least `min_block_id`)
+    /// from `block_id_counter` and inserts `record` under it, in one
+    /// transaction so concurrent requests can't race on the counter.
+    ///
+    /// A row for `record.tx_list_hash` only blocks the reservation while it's
+    /// still unexpired *and* has a live signer working on it; a prior
+    /// assignment that expired, or one whose signer never called
+    /// [`Self::set_signed_payload`] within [`STALE_RESERVATION_TIMEOUT_MS`],
+    /// is overwritten instead. Returns `Ok(None)` only when a genuinely live
+    /// assignment for that `txListHash` already exists.
+    pub async fn reserve(
+        &self,
+        min_block_id: u64,
+        record: &AssignmentRecord,
+    ) -> anyhow::Result<Option<u64>> {
+        let now = Utc::now().timestamp_millis();
+        let stale_before = now - STALE_RESERVATION_TIMEOUT_MS;
+
+        let mut tx = self.pool.begin().await?;
+
+        let (next_id,): (i64,) = sqlx::query_as(
+            "UPDATE block_id_counter \
+             SET next_id = (CASE WHEN next_id > $1 THEN next_id ELSE $1 END) + 1 \
+             RETURNING next_id",
+        )
+        .bind(min_block_id as i64)
+        .fetch_one(&mut *tx)
+        .await?;
+        let max_block_id = (next_id - 1) as u64;
+
+        let reserved: Option<(i64,)> = sqlx::query_as(
+            "INSERT INTO assignments (max_block_id, tx_list_hash, prover, expiry, tier_fees, signed_payload, reserved_at) \
+             VALUES ($1, $2, $3, $4, $5, NULL, $6) \
+             ON CONFLICT (tx_list_hash) DO UPDATE SET \
+                 max_block_id = excluded.max_block_id, \
+                 prover = excluded.prover, \
+                 expiry = excluded.expiry, \
+                 tier_fees = excluded.tier_fees, \
+                 signed_payload = NULL, \
+                 reserved_at = excluded.reserved_at \
+             WHERE assignments.expiry <= $7 \
+                OR (assignments.signed_payload IS NULL AND assignments.reserved_at <= $8) \
+             RETURNING max_block_id",
+        )
+        .bind(max_block_id as i64)
+        .bind(record.tx_list_hash.as_bytes())
+        .bind(record.prover.as_bytes())
+        .bind(record.expiry as i64)
+        .bind(&record.tier_fees)
+        .bind(now)
+        .bind(now)
+        .bind(stale_before)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(reserved.map(|_| max_block_id))
+    }
+
+    /// Attaches the signed payload once signing (which needs `max_block_id`
+    /// from [`Self::reserve`]) has completed.
+    pub async fn set_signed_payload(
+        &self,
+        max_block_id: u64,
+        signed_payload: &[u8],
+    ) -> anyhow::Result<()> {
+        sqlx::query("UPDATE assignments SET signed_payload = $1 WHERE max_block_id = $2")
+            .bind(signed_payload)
+            .bind(max_block_id as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Rolls back a reservation that never got a signed payload (e.g. signing
+    /// failed), immediately freeing its `txListHash` instead of waiting out
+    /// [`STALE_RESERVATION_TIMEOUT_MS`].
+    pub async fn release(&self, max_block_id: u64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM assignments WHERE max_block_id = $1 AND signed_payload IS NULL")
+            .bind(max_block_id as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Deletes assignments whose expiry has passed, returning how many were pruned.
+    pub async fn prune_expired(&self) -> anyhow::Result<u64> {
+        let now = Utc::now().timestamp_millis();
+
+        let result = sqlx::query("DELETE FROM assignments WHERE expiry <= $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Prunes expired assignments on `interval` until the process exits.
+    pub async fn run_pruning_task(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match self.prune_expired().await {
+                Ok(0) => {}
+                Ok(pruned) => info!(target: "db", description = "Pruned expired assignments", pruned),
+                Err(err) => error!(target: "db", description = "Failed to prune expired assignments", error = %err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn memory_store() -> AssignmentStore {
+        AssignmentStore::connect("sqlite::memory:")
+            .await
+            .expect("failed to connect to in-memory sqlite")
+    }
+
+    fn record(tx_list_hash: H256, expiry_ms_from_now: i64) -> AssignmentRecord {
+        AssignmentRecord {
+            tx_list_hash,
+            prover: H160::repeat_byte(0x01),
+            expiry: (Utc::now().timestamp_millis() + expiry_ms_from_now) as u64,
+            tier_fees: "".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reserve_allocates_unique_ids_for_distinct_requests() {
+        let store = memory_store().await;
+
+        let first = store
+            .reserve(1, &record(H256::repeat_byte(0x01), 60_000))
+            .await
+            .expect("reserve")
+            .expect("first reservation should succeed");
+        let second = store
+            .reserve(1, &record(H256::repeat_byte(0x02), 60_000))
+            .await
+            .expect("reserve")
+            .expect("second reservation should succeed");
+
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn reserve_rejects_replay_of_an_unexpired_tx_list_hash() {
+        let store = memory_store().await;
+        let tx_list_hash = H256::repeat_byte(0x42);
+
+        let first = store
+            .reserve(1, &record(tx_list_hash, 60_000))
+            .await
+            .expect("reserve");
+        assert!(first.is_some());
+
+        let replay = store
+            .reserve(1, &record(tx_list_hash, 60_000))
+            .await
+            .expect("reserve");
+        assert!(
+            replay.is_none(),
+            "an unexpired txListHash must not be reassigned"
+        );
+    }
+
+    #[tokio::test]
+    async fn reserve_reclaims_an_expired_tx_list_hash() {
+        let store = memory_store().await;
+        let tx_list_hash = H256::repeat_byte(0x77);
+
+        let first = store
+            .reserve(1, &record(tx_list_hash, -1))
+            .await
+            .expect("reserve");
+        assert!(first.is_some());
+
+        let retry = store
+            .reserve(1, &record(tx_list_hash, 60_000))
+            .await
+            .expect("reserve");
+        assert!(
+            retry.is_some(),
+            "an expired txListHash should be reclaimable"
+        );
+    }
+
+    #[tokio::test]
+    async fn release_frees_an_unsigned_reservation() {
+        let store = memory_store().await;
+        let tx_list_hash = H256::repeat_byte(0x99);
+
+        let max_block_id = store
+            .reserve(1, &record(tx_list_hash, 60_000))
+            .await
+            .expect("reserve")
+            .expect("reservation should succeed");
+
+        store.release(max_block_id).await.expect("release");
+
+        let retry = store
+            .reserve(1, &record(tx_list_hash, 60_000))
+            .await
+            .expect("reserve");
+        assert!(
+            retry.is_some(),
+            "releasing an unsigned reservation should free its txListHash"
+        );
+    }
+}
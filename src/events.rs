@@ -0,0 +1,110 @@
+use std::convert::Infallible;
+
+use axum::extract::{Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::{debug_handler, routing::get, Router};
+use ethereum_types::H256;
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::AppState;
+
+/// Lifecycle events for a proof assignment, streamed to subscribers over
+/// `/events`. Only `Assigned` is published in this crate (by
+/// `create_assignment` after signing); nothing here sends the other variants.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum AssignmentEvent {
+    Assigned {
+        #[schema(example = "0x0...0", value_type = String)]
+        tx_list_hash: H256,
+    },
+    ProofRequested {
+        #[schema(example = "0x0...0", value_type = String)]
+        tx_list_hash: H256,
+    },
+    Proven {
+        #[schema(example = "0x0...0", value_type = String)]
+        tx_list_hash: H256,
+    },
+    Expired {
+        #[schema(example = "0x0...0", value_type = String)]
+        tx_list_hash: H256,
+    },
+    Rejected {
+        #[schema(example = "0x0...0", value_type = String)]
+        tx_list_hash: H256,
+    },
+}
+
+impl AssignmentEvent {
+    pub fn tx_list_hash(&self) -> H256 {
+        match self {
+            AssignmentEvent::Assigned { tx_list_hash }
+            | AssignmentEvent::ProofRequested { tx_list_hash }
+            | AssignmentEvent::Proven { tx_list_hash }
+            | AssignmentEvent::Expired { tx_list_hash }
+            | AssignmentEvent::Rejected { tx_list_hash } => *tx_list_hash,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct EventsQuery {
+    #[schema(example = "0x0...0", value_type = String)]
+    tx_list_hash: Option<H256>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    params(
+        ("txListHash" = Option<String>, Query, description = "Only stream events for this assignment's txListHash"),
+    ),
+    responses(
+        (status = 200, description = "Server-sent stream of assignment lifecycle events", body = AssignmentEvent),
+    )
+)]
+#[debug_handler(state = AppState)]
+async fn subscribe(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = BroadcastStream::new(state.assignment_events.subscribe())
+        .filter_map(move |event| event.ok())
+        .filter(move |event: &AssignmentEvent| match query.tx_list_hash {
+            Some(tx_list_hash) => event.tx_list_hash() == tx_list_hash,
+            None => true,
+        })
+        .map(|event| {
+            Ok(Event::default()
+                .event(event_name(&event))
+                .json_data(event)
+                .unwrap_or_default())
+        });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+fn event_name(event: &AssignmentEvent) -> &'static str {
+    match event {
+        AssignmentEvent::Assigned { .. } => "Assigned",
+        AssignmentEvent::ProofRequested { .. } => "ProofRequested",
+        AssignmentEvent::Proven { .. } => "Proven",
+        AssignmentEvent::Expired { .. } => "Expired",
+        AssignmentEvent::Rejected { .. } => "Rejected",
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(subscribe), components(schemas(AssignmentEvent)))]
+pub struct EventsDoc;
+
+pub fn create_router() -> Router<AppState> {
+    Router::new().route("/", get(subscribe))
+}
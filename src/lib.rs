@@ -1,17 +1,40 @@
+use std::collections::{HashSet, VecDeque};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use assignment::AssignmentDoc;
 use axum::{debug_handler, http::StatusCode, routing::get, Json, Router};
-use p256::ecdsa::SigningKey;
+use bindings::TaikoL1;
+use dashmap::DashMap;
+use db::AssignmentStore;
+use ethers_core::utils::keccak256;
+use ethers_providers::{Http, Provider};
+use events::{AssignmentEvent, EventsDoc};
+use k256::ecdsa::{SigningKey, VerifyingKey};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
 use rand::rngs::ThreadRng;
 use serde_repr::Deserialize_repr;
 use status::StatusDoc;
+use tokio::sync::{broadcast, Semaphore};
 use tower_http::trace::{self, TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use utoipa::{OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 
 mod assignment;
+mod bindings;
+mod db;
+mod events;
 mod status;
 
+/// Capacity of the in-memory assignment lifecycle event channel; subscribers
+/// that fall this far behind miss the oldest events.
+const ASSIGNMENT_EVENTS_CAPACITY: usize = 256;
+
+/// How often the pruning task sweeps expired assignments out of the database.
+const PRUNE_EXPIRED_ASSIGNMENTS_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct AppState {
@@ -24,41 +47,103 @@ pub struct AppState {
     max_expiry: u64,
     max_slippage: u64,
     max_proposed_in: u64,
-    propose_concurrency_guard: (),
+    propose_concurrency_guard: Arc<Semaphore>,
+    rate_limiter: Arc<DashMap<IpAddr, VecDeque<Instant>>>,
+    requests_per_minute: usize,
+    trusted_proxies: Arc<HashSet<IpAddr>>,
+    assignment_events: broadcast::Sender<AssignmentEvent>,
     taiko_l1_address: ethereum_types::H160,
-    assignment_hook_address: ethereum_types::H160,
-    // rpc:                      *rpc.Client,
-    // protocol_configs:          *bindings.TaikoDataConfig,
+    rpc: Provider<Http>,
     liveness_bond: u64,
     is_guardian: bool,
-    // db
+    assignments: AssignmentStore,
 }
 
-impl Default for AppState {
-    fn default() -> Self {
+/// Derives the Ethereum address (keccak256 of the uncompressed public key,
+/// last 20 bytes) that corresponds to a secp256k1 signing key.
+fn prover_address_from_signing_key(signing_key: &SigningKey) -> ethereum_types::H160 {
+    let verifying_key = VerifyingKey::from(signing_key);
+    let encoded_point = verifying_key.to_encoded_point(false);
+    let hash = keccak256(&encoded_point.as_bytes()[1..]);
+
+    ethereum_types::H160::from_slice(&hash[12..])
+}
+
+impl AppState {
+    /// Builds the server state, then queries `TaikoL1::getConfig` on L1 so
+    /// `liveness_bond`, `max_proposed_in` and `max_slippage` track the live
+    /// protocol configuration instead of drifting from hard-coded defaults.
+    pub async fn new() -> anyhow::Result<Self> {
         let mut rng = ThreadRng::default();
         let prover_private_key = SigningKey::random(&mut rng);
+        let prover_address = prover_address_from_signing_key(&prover_private_key);
+        let rpc = Provider::<Http>::try_from(ensure_env("L1_RPC_URL"))?;
+        let max_capacity = ensure_env("MAX_CAPACITY").parse::<usize>()?;
+        let requests_per_minute = ensure_env("REQUESTS_PER_MINUTE").parse::<usize>()?;
+        let trusted_proxies = std::env::var("TRUSTED_PROXY_IPS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+            .collect::<HashSet<_>>();
+        let (assignment_events, _) = broadcast::channel(ASSIGNMENT_EVENTS_CAPACITY);
+
+        let taiko_l1_address: ethereum_types::H160 = ensure_env("TAIKO_L1_ADDRESS").parse()?;
+        let taiko_l1 = TaikoL1::new(taiko_l1_address, Arc::new(rpc.clone()));
+        let protocol_config = taiko_l1.get_config().call().await?;
 
-        Self {
+        let assignments = AssignmentStore::connect(&ensure_env("DATABASE_URL")).await?;
+        tokio::spawn(
+            assignments
+                .clone()
+                .run_pruning_task(PRUNE_EXPIRED_ASSIGNMENTS_INTERVAL),
+        );
+
+        Ok(Self {
             prover_private_key,
-            prover_address: ethereum_types::H160::zero(),
+            prover_address,
+            rpc,
             min_optimistic_tier_fee: 0,
             min_sgx_tier_fee: 0,
             min_pse_zkevm_tier_fee: 0,
             min_sgx_and_pse_zkevm_tier_fee: 0,
             max_expiry: 0,
-            max_slippage: 0,
-            max_proposed_in: 0,
-            propose_concurrency_guard: (),
-            taiko_l1_address: ethereum_types::H160::zero(),
-            assignment_hook_address: ethereum_types::H160::zero(),
-            liveness_bond: 0,
+            max_slippage: protocol_config.max_slippage,
+            max_proposed_in: protocol_config.max_proposed_in,
+            propose_concurrency_guard: Arc::new(Semaphore::new(max_capacity)),
+            rate_limiter: Arc::new(DashMap::new()),
+            requests_per_minute,
+            trusted_proxies: Arc::new(trusted_proxies),
+            assignment_events,
+            taiko_l1_address,
+            liveness_bond: protocol_config.liveness_bond.as_u64(),
             is_guardian: false,
+            assignments,
+        })
+    }
+
+    /// Records a request from `ip` and returns `false` once it has exceeded
+    /// `requests_per_minute` requests within the trailing 60-second window.
+    fn check_rate_limit(&self, ip: IpAddr) -> bool {
+        let now = Instant::now();
+        let mut window = self.rate_limiter.entry(ip).or_default();
+
+        while window
+            .front()
+            .is_some_and(|seen_at| now.duration_since(*seen_at) > Duration::from_secs(60))
+        {
+            window.pop_front();
         }
+
+        if window.len() >= self.requests_per_minute {
+            return false;
+        }
+
+        window.push_back(now);
+        true
     }
 }
 
-#[derive(Debug, Deserialize_repr, PartialEq, Eq, ToSchema)]
+#[derive(Debug, Clone, Copy, Deserialize_repr, PartialEq, Eq, ToSchema)]
 #[repr(u8)]
 enum Tier {
     Optimistic,
@@ -124,6 +209,7 @@ pub fn create_router() -> Router<AppState> {
     let docs = [
         AssignmentDoc::openapi(),
         StatusDoc::openapi(),
+        EventsDoc::openapi(),
     ];
     
     for sub_doc in docs {
@@ -138,6 +224,7 @@ pub fn create_router() -> Router<AppState> {
         .route("/healthz", get(health))
         .nest("/status", status::create_router())
         .nest("/assignment", assignment::create_router())
+        .nest("/events", events::create_router())
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(trace::DefaultMakeSpan::new().level(tracing::Level::INFO))
@@ -158,3 +245,102 @@ pub fn init_tracing() {
 pub fn ensure_env(name: &str) -> String {
     std::env::var(name).expect(&format!("{name} is not set"))
 }
+
+/// Resolves the proposer's real IP, preferring `X-Forwarded-For` / `X-Real-IP`
+/// over the raw TCP peer address, but only when that peer is a configured
+/// `trusted_proxies` address — otherwise those headers are attacker-controlled
+/// and would let a client spoof a new identity on every request to dodge the
+/// per-IP rate limiter.
+pub(crate) fn client_ip(
+    connect_info: std::net::SocketAddr,
+    headers: &axum::http::HeaderMap,
+    trusted_proxies: &HashSet<IpAddr>,
+) -> IpAddr {
+    if !trusted_proxies.contains(&connect_info.ip()) {
+        return connect_info.ip();
+    }
+
+    headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .and_then(|value| value.trim().parse().ok())
+        .or_else(|| {
+            headers
+                .get("x-real-ip")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+        })
+        .unwrap_or_else(|| connect_info.ip())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    use axum::http::HeaderMap;
+
+    use super::*;
+
+    fn headers_with_forwarded_for(ip: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", ip.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn client_ip_honors_forwarded_for_from_a_trusted_proxy() {
+        let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let connect_info = SocketAddr::new(proxy, 443);
+        let trusted_proxies = HashSet::from([proxy]);
+        let headers = headers_with_forwarded_for("203.0.113.7");
+
+        let resolved = client_ip(connect_info, &headers, &trusted_proxies);
+
+        assert_eq!(resolved, IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)));
+    }
+
+    #[test]
+    fn client_ip_ignores_forwarded_for_from_an_untrusted_peer() {
+        let peer = IpAddr::V4(Ipv4Addr::new(198, 51, 100, 2));
+        let connect_info = SocketAddr::new(peer, 443);
+        let trusted_proxies = HashSet::new();
+        let headers = headers_with_forwarded_for("203.0.113.7");
+
+        let resolved = client_ip(connect_info, &headers, &trusted_proxies);
+
+        assert_eq!(resolved, peer);
+    }
+
+    #[tokio::test]
+    async fn check_rate_limit_blocks_once_the_window_is_exhausted() {
+        let state = AppState {
+            prover_private_key: SigningKey::random(&mut rand::rngs::ThreadRng::default()),
+            prover_address: ethereum_types::H160::zero(),
+            min_optimistic_tier_fee: 0,
+            min_sgx_tier_fee: 0,
+            min_pse_zkevm_tier_fee: 0,
+            min_sgx_and_pse_zkevm_tier_fee: 0,
+            max_expiry: 0,
+            max_slippage: 0,
+            max_proposed_in: 0,
+            propose_concurrency_guard: Arc::new(Semaphore::new(1)),
+            rate_limiter: Arc::new(DashMap::new()),
+            requests_per_minute: 2,
+            trusted_proxies: Arc::new(HashSet::new()),
+            assignment_events: broadcast::channel(ASSIGNMENT_EVENTS_CAPACITY).0,
+            taiko_l1_address: ethereum_types::H160::zero(),
+            rpc: Provider::<Http>::try_from("http://localhost:8545").unwrap(),
+            liveness_bond: 0,
+            is_guardian: false,
+            assignments: AssignmentStore::connect("sqlite::memory:")
+                .await
+                .expect("failed to connect to in-memory sqlite"),
+        };
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+
+        assert!(state.check_rate_limit(ip));
+        assert!(state.check_rate_limit(ip));
+        assert!(!state.check_rate_limit(ip));
+    }
+}
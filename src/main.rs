@@ -1,4 +1,6 @@
-use prover_api::{create_router, init_tracing};
+use std::net::SocketAddr;
+
+use prover_api::{create_router, init_tracing, AppState};
 use tokio::net::TcpListener;
 use tracing::debug;
 
@@ -8,7 +10,8 @@ async fn main() -> anyhow::Result<()> {
 
     init_tracing();
 
-    let router = create_router().with_state(Default::default());
+    let state = AppState::new().await?;
+    let router = create_router().with_state(state);
 
     let port = std::env::var("PORT").unwrap_or("3000".to_string());
 
@@ -16,7 +19,11 @@ async fn main() -> anyhow::Result<()> {
 
     debug!("Listening on: {}", listener.local_addr()?);
 
-    axum::serve(listener, router).await?;
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
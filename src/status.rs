@@ -17,6 +17,8 @@ struct Status {
     max_expiry: u64,
     #[schema(example = "0x0...0")]
     prover: String,
+    #[schema(example = 10)]
+    free_capacity: usize,
 }
 
 #[utoipa::path(
@@ -35,6 +37,7 @@ async fn get_status(
         min_pse_zkevm_tier_fee,
         max_expiry,
         prover_address,
+        propose_concurrency_guard,
         ..
     }): State<AppState>,
 ) -> ApiResult<Status> {
@@ -44,6 +47,7 @@ async fn get_status(
         min_pse_zkevm_tier_fee,
         max_expiry,
         prover: format!("{prover_address:#?}"),
+        free_capacity: propose_concurrency_guard.available_permits(),
     }))
 }
 